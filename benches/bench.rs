@@ -1,14 +1,42 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use rdu::get_disk_usage;
+use rdu::{get_disk_usage, PathFilters, ScanOptions};
 use std::path::PathBuf;
 
 /// Benchmark allocating space for disk usage on a single thread. Do not benchmark printing, since that
 /// is dependent on the machine and not code optimizations.
 fn get_disk_usage_single_thread(c: &mut Criterion) {
+    let filters = PathFilters::none();
+    let options = ScanOptions {
+        depth: u16::MAX,
+        threads: Some(1),
+        dedupe: false,
+        filters: &filters,
+        disk_usage: false,
+    };
     c.bench_function("Disk Usage On Single Thread", |b| {
-        b.iter(|| get_disk_usage(PathBuf::from("./"), u16::MAX))
+        b.iter(|| get_disk_usage(PathBuf::from("./"), &options))
     });
 }
 
-criterion_group!(benches, get_disk_usage_single_thread);
+/// Benchmark the parallel work-stealing walker using all available CPUs, to measure the
+/// speedup over [`get_disk_usage_single_thread`].
+fn get_disk_usage_multi_thread(c: &mut Criterion) {
+    let filters = PathFilters::none();
+    let options = ScanOptions {
+        depth: u16::MAX,
+        threads: None,
+        dedupe: false,
+        filters: &filters,
+        disk_usage: false,
+    };
+    c.bench_function("Disk Usage On Multiple Threads", |b| {
+        b.iter(|| get_disk_usage(PathBuf::from("./"), &options))
+    });
+}
+
+criterion_group!(
+    benches,
+    get_disk_usage_single_thread,
+    get_disk_usage_multi_thread
+);
 criterion_main!(benches);
@@ -0,0 +1,383 @@
+//! Binary scan-cache format: persist a completed scan to disk so large trees don't have to be
+//! re-walked, and diff a fresh scan against a cached one.
+//!
+//! The on-disk layout is a fixed header (magic bytes, a format version, the root path's length,
+//! and an offset/length slice pointing at the node table), followed by the root path bytes,
+//! followed by a table of fixed-size node records, followed by a trailing variable-length path
+//! blob — the same "fixed header + fixed-size nodes + variable-length path heap" layout
+//! version-2 dirstate formats use for fast, low-allocation reads. Paths are sorted and
+//! delta/prefix-encoded against the previous path in the table (a shared-prefix length plus the
+//! differing suffix) so the blob doesn't repeat long shared directory prefixes.
+
+use crate::PathSizeMetadata;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+const MAGIC: &[u8; 4] = b"RDU\x01";
+const FORMAT_VERSION: u32 = 1;
+
+/// `size` + `depth` + `prefix_len` + `suffix_offset` + `suffix_len`, in bytes.
+const NODE_RECORD_SIZE: usize = 8 + 2 + 2 + 4 + 4;
+
+/// A single fixed-size on-disk node record: a size, a depth, and a delta-encoded pointer into
+/// the trailing path blob.
+struct RawNode {
+    size: u64,
+    depth: u16,
+    prefix_len: u16,
+    suffix_offset: u32,
+    suffix_len: u32,
+}
+
+fn write_u16(w: &mut impl Write, v: u16) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn write_u32(w: &mut impl Write, v: u32) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn write_u64(w: &mut impl Write, v: u64) -> io::Result<()> {
+    w.write_all(&v.to_le_bytes())
+}
+
+fn read_u16(r: &mut impl Read) -> io::Result<u16> {
+    let mut bytes = [0u8; 2];
+    r.read_exact(&mut bytes)?;
+    Ok(u16::from_le_bytes(bytes))
+}
+
+fn read_u32(r: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    r.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(r: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    r.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Number of leading bytes `a` and `b` have in common.
+fn shared_prefix_len(a: &str, b: &str) -> usize {
+    a.bytes().zip(b.bytes()).take_while(|(x, y)| x == y).count()
+}
+
+/// Write a completed scan of `root` to `cache_path` in the binary scan-cache format.
+pub fn save_scan(cache_path: &Path, root: &Path, data: &[PathSizeMetadata]) -> io::Result<()> {
+    let mut sorted: Vec<&PathSizeMetadata> = data.iter().collect();
+    sorted.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut blob: Vec<u8> = Vec::new();
+    let mut nodes: Vec<RawNode> = Vec::with_capacity(sorted.len());
+    let mut prev = String::new();
+
+    for item in &sorted {
+        let current = item.path.to_string_lossy().into_owned();
+        let prefix_len = shared_prefix_len(&prev, &current).min(u16::MAX as usize);
+        let suffix = &current.as_bytes()[prefix_len..];
+
+        nodes.push(RawNode {
+            size: item.size,
+            depth: item.depth,
+            prefix_len: prefix_len as u16,
+            suffix_offset: blob.len() as u32,
+            suffix_len: suffix.len() as u32,
+        });
+        blob.extend_from_slice(suffix);
+        prev = current;
+    }
+
+    let root_bytes = root.to_string_lossy().into_owned().into_bytes();
+    // magic(4) + version(4) + root_len(4) + node_table_offset(8) + node_table_len(8)
+    let header_size = 4 + 4 + 4 + 8 + 8;
+    let node_table_offset = (header_size + root_bytes.len()) as u64;
+    let node_table_len = (nodes.len() * NODE_RECORD_SIZE) as u64;
+
+    let mut w = BufWriter::new(File::create(cache_path)?);
+
+    w.write_all(MAGIC)?;
+    write_u32(&mut w, FORMAT_VERSION)?;
+    write_u32(&mut w, root_bytes.len() as u32)?;
+    write_u64(&mut w, node_table_offset)?;
+    write_u64(&mut w, node_table_len)?;
+    w.write_all(&root_bytes)?;
+
+    for node in &nodes {
+        write_u64(&mut w, node.size)?;
+        write_u16(&mut w, node.depth)?;
+        write_u16(&mut w, node.prefix_len)?;
+        write_u32(&mut w, node.suffix_offset)?;
+        write_u32(&mut w, node.suffix_len)?;
+    }
+
+    w.write_all(&blob)?;
+    w.flush()
+}
+
+/// Read a scan previously written by [`save_scan`], returning the scanned root path and its
+/// metadata without re-walking the tree.
+pub fn load_scan(cache_path: &Path) -> io::Result<(PathBuf, Vec<PathSizeMetadata>)> {
+    let mut r = BufReader::new(File::open(cache_path)?);
+
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "not an rdu scan cache",
+        ));
+    }
+
+    let version = read_u32(&mut r)?;
+    if version != FORMAT_VERSION {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsupported scan cache format version {version}"),
+        ));
+    }
+
+    let root_len = read_u32(&mut r)? as usize;
+    let _node_table_offset = read_u64(&mut r)?;
+    let node_table_len = read_u64(&mut r)? as usize;
+
+    let mut root_bytes = vec![0u8; root_len];
+    r.read_exact(&mut root_bytes)?;
+    let root = PathBuf::from(String::from_utf8_lossy(&root_bytes).into_owned());
+
+    let node_count = node_table_len / NODE_RECORD_SIZE;
+    let mut nodes = Vec::with_capacity(node_count);
+    for _ in 0..node_count {
+        nodes.push(RawNode {
+            size: read_u64(&mut r)?,
+            depth: read_u16(&mut r)?,
+            prefix_len: read_u16(&mut r)?,
+            suffix_offset: read_u32(&mut r)?,
+            suffix_len: read_u32(&mut r)?,
+        });
+    }
+
+    let mut blob = Vec::new();
+    r.read_to_end(&mut blob)?;
+
+    let mut prev = String::new();
+    let mut data = Vec::with_capacity(node_count);
+    for node in nodes {
+        let prefix_len = node.prefix_len as usize;
+        if prefix_len > prev.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "scan cache node references a prefix longer than the previous path",
+            ));
+        }
+
+        let suffix_start = node.suffix_offset as usize;
+        let suffix_end = suffix_start + node.suffix_len as usize;
+        if suffix_end > blob.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "scan cache node references a path suffix outside the path blob",
+            ));
+        }
+
+        let mut bytes = prev.as_bytes()[..prefix_len].to_vec();
+        bytes.extend_from_slice(&blob[suffix_start..suffix_end]);
+        let current = String::from_utf8_lossy(&bytes).into_owned();
+
+        data.push(PathSizeMetadata {
+            path: PathBuf::from(&current),
+            size: node.size,
+            depth: node.depth,
+            inode: None,
+        });
+        prev = current;
+    }
+
+    Ok((root, data))
+}
+
+/// A single path whose size differs between a cached scan and a fresh one.
+pub struct ScanDiff {
+    pub path: PathBuf,
+    pub previous_size: u64,
+    pub current_size: u64,
+}
+
+/// Compare a cached scan against a freshly rescanned one, returning only the paths whose size
+/// changed (added, removed, or resized). A path missing from one side is treated as size zero.
+pub fn diff_scan(cached: &[PathSizeMetadata], fresh: &[PathSizeMetadata]) -> Vec<ScanDiff> {
+    let cached_sizes: HashMap<&PathBuf, u64> = cached.iter().map(|m| (&m.path, m.size)).collect();
+    let fresh_sizes: HashMap<&PathBuf, u64> = fresh.iter().map(|m| (&m.path, m.size)).collect();
+
+    let mut paths: Vec<&PathBuf> = cached_sizes.keys().chain(fresh_sizes.keys()).copied().collect();
+    paths.sort();
+    paths.dedup();
+
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let previous_size = *cached_sizes.get(path).unwrap_or(&0);
+            let current_size = *fresh_sizes.get(path).unwrap_or(&0);
+
+            (previous_size != current_size).then(|| ScanDiff {
+                path: path.clone(),
+                previous_size,
+                current_size,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A cache path under the system temp dir that's unique per test invocation, so concurrent
+    /// test runs don't clobber each other's files.
+    fn temp_cache_path(label: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        std::env::temp_dir().join(format!("rdu-test-{label}-{}-{n}.bin", std::process::id()))
+    }
+
+    #[test]
+    fn save_scan_then_load_scan_round_trips() {
+        let cache_path = temp_cache_path("round-trip");
+        let root = PathBuf::from("/tmp/example");
+        let data = vec![
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example"),
+                size: 30,
+                depth: 0,
+                inode: None,
+            },
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/src"),
+                size: 20,
+                depth: 1,
+                inode: None,
+            },
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/src/main.rs"),
+                size: 20,
+                depth: 2,
+                inode: None,
+            },
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/readme.md"),
+                size: 10,
+                depth: 1,
+                inode: None,
+            },
+        ];
+
+        save_scan(&cache_path, &root, &data).expect("save_scan should succeed");
+        let (loaded_root, mut loaded_data) =
+            load_scan(&cache_path).expect("load_scan should succeed");
+        std::fs::remove_file(&cache_path).ok();
+
+        assert_eq!(loaded_root, root);
+
+        loaded_data.sort_by(|a, b| a.path.cmp(&b.path));
+        let mut expected = data;
+        expected.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert_eq!(loaded_data.len(), expected.len());
+        for (loaded, expected) in loaded_data.iter().zip(expected.iter()) {
+            assert_eq!(loaded.path, expected.path);
+            assert_eq!(loaded.size, expected.size);
+            assert_eq!(loaded.depth, expected.depth);
+        }
+    }
+
+    #[test]
+    fn load_scan_rejects_truncated_path_blob() {
+        let cache_path = temp_cache_path("truncated-blob");
+        let root = PathBuf::from("/tmp/example");
+        let data = vec![PathSizeMetadata {
+            path: PathBuf::from("/tmp/example/a-long-file-name.txt"),
+            size: 1,
+            depth: 1,
+            inode: None,
+        }];
+
+        save_scan(&cache_path, &root, &data).expect("save_scan should succeed");
+
+        let mut bytes = std::fs::read(&cache_path).expect("cache file should exist");
+        bytes.truncate(bytes.len() - 4);
+        std::fs::write(&cache_path, &bytes).expect("failed to write truncated cache");
+
+        let result = load_scan(&cache_path);
+        std::fs::remove_file(&cache_path).ok();
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn diff_scan_reports_added_removed_and_resized_paths() {
+        let cached = vec![
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/unchanged.txt"),
+                size: 10,
+                depth: 1,
+                inode: None,
+            },
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/resized.txt"),
+                size: 10,
+                depth: 1,
+                inode: None,
+            },
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/removed.txt"),
+                size: 5,
+                depth: 1,
+                inode: None,
+            },
+        ];
+        let fresh = vec![
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/unchanged.txt"),
+                size: 10,
+                depth: 1,
+                inode: None,
+            },
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/resized.txt"),
+                size: 20,
+                depth: 1,
+                inode: None,
+            },
+            PathSizeMetadata {
+                path: PathBuf::from("/tmp/example/added.txt"),
+                size: 7,
+                depth: 1,
+                inode: None,
+            },
+        ];
+
+        let mut diffs = diff_scan(&cached, &fresh);
+        diffs.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert_eq!(diffs.len(), 3);
+
+        let added = &diffs[0];
+        assert_eq!(added.path, PathBuf::from("/tmp/example/added.txt"));
+        assert_eq!(added.previous_size, 0);
+        assert_eq!(added.current_size, 7);
+
+        let removed = &diffs[1];
+        assert_eq!(removed.path, PathBuf::from("/tmp/example/removed.txt"));
+        assert_eq!(removed.previous_size, 5);
+        assert_eq!(removed.current_size, 0);
+
+        let resized = &diffs[2];
+        assert_eq!(resized.path, PathBuf::from("/tmp/example/resized.txt"));
+        assert_eq!(resized.previous_size, 10);
+        assert_eq!(resized.current_size, 20);
+    }
+}
@@ -0,0 +1,88 @@
+//! Include/exclude path filtering for [`crate::get_disk_usage`].
+//!
+//! Patterns are glob-style by default (`*.log`), with an opt-in mode that compiles them as
+//! regular expressions instead. Excludes prune traversal outright: a directory matching an
+//! exclude pattern is never `read_dir`'d, so its contents are skipped rather than merely
+//! hidden from output. Includes, when present, restrict which files contribute to directory
+//! totals without pruning traversal, so a matching file nested under unmatched directories
+//! still rolls its size up into its parents.
+
+use glob::Pattern;
+use regex::Regex;
+use std::path::Path;
+
+/// A single compiled include/exclude pattern.
+enum Matcher {
+    Glob(Pattern),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn compile(pattern: &str, regex: bool) -> Result<Matcher, String> {
+        if regex {
+            Regex::new(pattern)
+                .map(Matcher::Regex)
+                .map_err(|e| format!("invalid regex pattern '{pattern}': {e}"))
+        } else {
+            Pattern::new(pattern)
+                .map(Matcher::Glob)
+                .map_err(|e| format!("invalid glob pattern '{pattern}': {e}"))
+        }
+    }
+
+    fn is_match(&self, path: &Path) -> bool {
+        let path_str = path.to_string_lossy();
+        match self {
+            Matcher::Glob(pattern) => {
+                pattern.matches(&path_str)
+                    || path
+                        .file_name()
+                        .map(|name| pattern.matches(&name.to_string_lossy()))
+                        .unwrap_or(false)
+            }
+            Matcher::Regex(regex) => regex.is_match(&path_str),
+        }
+    }
+}
+
+/// A compiled set of `--exclude`/`--include` patterns applied during traversal.
+pub struct PathFilters {
+    excludes: Vec<Matcher>,
+    includes: Vec<Matcher>,
+}
+
+impl PathFilters {
+    /// Compile `excludes` and `includes` as glob patterns, or as regular expressions when
+    /// `regex` is set.
+    pub fn new(excludes: &[String], includes: &[String], regex: bool) -> Result<PathFilters, String> {
+        let excludes = excludes
+            .iter()
+            .map(|p| Matcher::compile(p, regex))
+            .collect::<Result<Vec<_>, _>>()?;
+        let includes = includes
+            .iter()
+            .map(|p| Matcher::compile(p, regex))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(PathFilters { excludes, includes })
+    }
+
+    /// An empty filter set that excludes nothing and includes everything.
+    pub fn none() -> PathFilters {
+        PathFilters {
+            excludes: Vec::new(),
+            includes: Vec::new(),
+        }
+    }
+
+    /// Whether `path` should be pruned from traversal entirely.
+    pub fn is_excluded(&self, path: &Path) -> bool {
+        self.excludes.iter().any(|m| m.is_match(path))
+    }
+
+    /// Whether `path` should count toward directory totals. When no `--include` patterns
+    /// were given, everything not excluded counts.
+    pub fn is_included(&self, path: &Path) -> bool {
+        self.includes.is_empty() || self.includes.iter().any(|m| m.is_match(path))
+    }
+}
@@ -1,10 +1,81 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::io::IsTerminal;
 use std::{fs, path::PathBuf};
+use terminal_size::{terminal_size, Width};
+
+mod cache;
+mod filter;
+mod parallel;
+
+pub use cache::{diff_scan, load_scan, save_scan, ScanDiff};
+pub use filter::PathFilters;
 
 pub struct PathSizeMetadata {
     path: PathBuf,
     size: u64,
-    depth: u16,
+    pub depth: u16,
+    /// A `(device, inode)` pair uniquely identifying the file this entry points to, used to
+    /// detect hard links so shared files aren't double-counted. `None` for directories and on
+    /// platforms where the identifier could not be determined.
+    inode: Option<(u64, u64)>,
+}
+
+/// Read the `(device, inode)` pair that identifies the file `metadata` describes, so hard
+/// links to the same file can be recognized regardless of which path reached them first.
+#[cfg(unix)]
+fn inode_key(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(windows)]
+fn inode_key(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::windows::fs::MetadataExt;
+    match (metadata.volume_serial_number(), metadata.file_index()) {
+        (Some(volume), Some(index)) => Some((volume as u64, index)),
+        _ => None,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn inode_key(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Number of bytes `path` actually occupies on disk, as opposed to [`fs::Metadata::len`]'s
+/// logical/apparent size. On Unix this is the allocated 512-byte block count times 512; on Windows it's
+/// the compressed/allocated size reported by `GetCompressedFileSizeW`. Sparse and compressed
+/// files can differ substantially from their apparent length.
+#[cfg(unix)]
+fn allocated_size(_path: &std::path::Path, metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.blocks() * 512
+}
+
+#[cfg(windows)]
+fn allocated_size(path: &std::path::Path, metadata: &fs::Metadata) -> u64 {
+    use std::os::windows::ffi::OsStrExt;
+    use windows_sys::Win32::Storage::FileSystem::GetCompressedFileSizeW;
+
+    let mut wide: Vec<u16> = path.as_os_str().encode_wide().collect();
+    wide.push(0);
+
+    let mut high: u32 = 0;
+    // SAFETY: `wide` is a valid, NUL-terminated UTF-16 string for the lifetime of this call.
+    let low = unsafe { GetCompressedFileSizeW(wide.as_ptr(), &mut high) };
+
+    if low == u32::MAX {
+        // GetCompressedFileSizeW failed (e.g. the path vanished mid-scan); fall back to the
+        // logical length rather than losing the entry.
+        metadata.len()
+    } else {
+        (u64::from(high) << 32) | u64::from(low)
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn allocated_size(_path: &std::path::Path, metadata: &fs::Metadata) -> u64 {
+    metadata.len()
 }
 
 pub enum Depth {
@@ -42,9 +113,26 @@ fn read_dir_contents(dir_path: &PathBuf) -> Result<impl Iterator<Item = PathBuf>
 
 /// Get directory size by recursively entering each directory within and summing the size of
 /// its children until there are no directories left.
+///
+/// `dedupe` and `seen` implement hard-link deduplication: when enabled, a file's size is only
+/// folded into its parent's total the first time its `(device, inode)` pair is observed.
+/// `seen` is threaded through the whole recursion (rather than reset per directory) so hard
+/// links across sibling directories are also recognized.
+///
+/// `filters` prunes traversal: an excluded directory is never descended into and its contents
+/// are skipped entirely, while an excluded or non-included file is skipped outright. Non-excluded
+/// directories themselves are always walked (even if they don't match `--include`) so that
+/// matching descendants further down still roll their size up into them.
+///
+/// `disk_usage` picks the size source: `true` uses allocated blocks on disk, `false` (the
+/// default) uses the file's logical/apparent length; see [`allocated_size`].
 fn get_dir_data<'a>(
     dir_path: PathBuf,
     depth: Depth,
+    dedupe: bool,
+    seen: &mut HashSet<(u64, u64)>,
+    filters: &PathFilters,
+    disk_usage: bool,
 ) -> Result<(Vec<PathSizeMetadata>, u64), &'a str> {
     if !dir_path.is_dir() {
         return Err("Provided path is not a directory");
@@ -61,18 +149,36 @@ fn get_dir_data<'a>(
     let mut size: u64 = 0;
 
     for path in paths {
+        if filters.is_excluded(&path) {
+            continue;
+        }
+
         if path.is_dir() {
-            match get_dir_data(path, Depth::Depth(current_depth + 1)) {
+            match get_dir_data(
+                path,
+                Depth::Depth(current_depth + 1),
+                dedupe,
+                seen,
+                filters,
+                disk_usage,
+            ) {
                 Ok(data) => {
                     metadata.extend(data.0);
                     size += data.1;
                 }
                 Err(_e) => {}
             }
-        } else {
-            match get_file_size(path, &Depth::Depth(current_depth + 1)) {
+        } else if filters.is_included(&path) {
+            match get_file_size(path, &Depth::Depth(current_depth + 1), disk_usage) {
                 Ok(data) => {
-                    size += data.size;
+                    let counts_toward_total = match (dedupe, data.inode) {
+                        (true, Some(inode)) => seen.insert(inode),
+                        _ => true,
+                    };
+
+                    if counts_toward_total {
+                        size += data.size;
+                    }
                     metadata.push(data);
                 }
                 Err(_e) => {}
@@ -87,6 +193,7 @@ fn get_dir_data<'a>(
             Depth::None => 0,
             Depth::Depth(d) => d,
         },
+        inode: None,
     });
 
     Ok((metadata, size))
@@ -99,15 +206,21 @@ fn get_dir_data<'a>(
 /// * `file_path` - A path to a file. If this path does not point to a file that is not a directory, an error is returned.
 /// * `depth` - A depth value to represent the current depth of the path from the starting directory. Can be borrowed instead
 /// of owned since the Depth enum contains only a u16, which is copyable by default.
-fn get_file_size(file_path: PathBuf, depth: &Depth) -> Result<PathSizeMetadata, &str> {
+/// * `disk_usage` - When `true`, report allocated blocks on disk instead of the logical length; see [`allocated_size`].
+fn get_file_size(file_path: PathBuf, depth: &Depth, disk_usage: bool) -> Result<PathSizeMetadata, &str> {
     if !file_path.is_file() {
         return Err("Get file size provided with a non-file path");
     }
 
     match fs::metadata(&file_path) {
         Ok(metadata) => Ok(PathSizeMetadata {
+            inode: inode_key(&metadata),
+            size: if disk_usage {
+                allocated_size(&file_path, &metadata)
+            } else {
+                metadata.len()
+            },
             path: file_path,
-            size: metadata.len(),
             depth: match depth {
                 Depth::None => 0,
                 Depth::Depth(d) => *d,
@@ -162,54 +275,275 @@ fn print_bytes(data: Vec<PathSizeMetadata>) {
     }
 }
 
+/// Format a byte count as human readable text (e.g. `4.2M`), shared by the flat
+/// human-readable printer and the tree view.
+fn human_readable_size(size: u64) -> String {
+    // truncate off digits until below the 4 digit count
+    let units: HashMap<u8, char> = HashMap::from([(0, 'B'), (1, 'K'), (2, 'M'), (3, 'G')]);
+    let mut truncate_count: u8 = 0;
+    let mut size: f64 = size as f64;
+    while size >= 1024_f64 {
+        size /= 1024_f64;
+        truncate_count += 1;
+    }
+
+    // if the count of digits is equal to 1 for the size, add a
+    // single decimal point, otherwise truncate all decimals
+    format!(
+        "{:.2$}{}",
+        size,
+        units.get(&truncate_count).unwrap_or(&'?'),
+        (size < 9.95) as usize
+    )
+}
+
 /// Print function in human readable format
 fn print_readable(data: Vec<PathSizeMetadata>) {
     // max digits will always be 3 + 1 character for the letter
     let max_digits: usize = 4;
-    let units: HashMap<u8, char> = HashMap::from([(0, 'B'), (1, 'K'), (2, 'M'), (3, 'G')]);
 
     for item in data {
-        // truncate off digits until below the 4 digit count
-        let mut truncate_count: u8 = 0;
-        let mut size: f64 = item.size as f64;
-        while size >= 1024_f64 {
-            size /= 1024_f64;
-            truncate_count += 1;
-        }
-
-        // if the count of digits is equal to 1 for the size, add a
-        // single decimal point, otherwise truncate all decimals
         println!(
             "{:<max_digits$}  {}",
-            format!(
-                "{:.2$}{}",
-                size,
-                units.get(&truncate_count).unwrap_or(&'?'),
-                (size < 9.95) as usize
-            ),
+            human_readable_size(item.size),
             item.path.display()
         );
     }
 }
 
+/// Box-drawing glyphs used to render [`print_tree`]'s nesting.
+const TREE_BRANCH: &str = "\u{251c}\u{2500}\u{2500} ";
+const TREE_LAST_BRANCH: &str = "\u{2514}\u{2500}\u{2500} ";
+const TREE_VERTICAL: &str = "\u{2502}   ";
+const TREE_BLANK: &str = "    ";
+
+/// Wrap `text` in an ANSI color code scaled to how large `size` is relative to `max_size`:
+/// green for the smallest entries, yellow in the middle, red for the largest.
+fn colorize_by_size(text: &str, size: u64, max_size: u64) -> String {
+    let ratio = if max_size == 0 {
+        0_f64
+    } else {
+        size as f64 / max_size as f64
+    };
+
+    let code = if ratio < 0.33 {
+        32 // green
+    } else if ratio < 0.66 {
+        33 // yellow
+    } else {
+        31 // red
+    };
+
+    format!("\x1b[{code}m{text}\x1b[0m")
+}
+
+/// Render a single tree row: the human readable size and the entry's file name, followed by a
+/// proportional usage bar right-aligned so it ends at column `term_width` (when the row fits)
+/// once `prefix_len` (the box-drawing indentation the caller will prepend) is accounted for.
+/// When `colorize` is set, the whole row is colored by relative size. When `show_bar` is `false`
+/// (stdout isn't a terminal), the bar is omitted entirely and only the size and name are rendered.
+fn format_tree_row(
+    item: &PathSizeMetadata,
+    max_size: u64,
+    term_width: usize,
+    prefix_len: usize,
+    colorize: bool,
+    show_bar: bool,
+) -> String {
+    let name = item
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| item.path.display().to_string());
+
+    let size_label = human_readable_size(item.size);
+
+    let label = format!("{size_label:<4}  {name}");
+
+    if !show_bar {
+        return label;
+    }
+
+    let label_len = label.chars().count();
+    let bar_width = term_width
+        .saturating_sub(prefix_len + label_len + 4)
+        .clamp(4, 30);
+
+    let filled = if max_size == 0 {
+        0
+    } else {
+        ((item.size as f64 / max_size as f64) * bar_width as f64).round() as usize
+    };
+    let filled = filled.min(bar_width);
+
+    let bar = format!("[{}{}]", "#".repeat(filled), "-".repeat(bar_width - filled));
+    let padding = term_width
+        .saturating_sub(prefix_len + label_len + bar_width + 2)
+        .max(2);
+    let row = format!("{label}{}{bar}", " ".repeat(padding));
+
+    if colorize {
+        colorize_by_size(&row, item.size, max_size)
+    } else {
+        row
+    }
+}
+
+/// Recursively print `path`'s children, indenting with `prefix` and extending it for
+/// grandchildren so the box-drawing connectors line up.
+fn print_tree_children(
+    path: &PathBuf,
+    children: &HashMap<PathBuf, Vec<&PathSizeMetadata>>,
+    prefix: &str,
+    max_size: u64,
+    term_width: usize,
+    colorize: bool,
+    show_bar: bool,
+) {
+    let Some(kids) = children.get(path) else {
+        return;
+    };
+
+    for (i, item) in kids.iter().enumerate() {
+        let is_last = i == kids.len() - 1;
+        let branch = if is_last { TREE_LAST_BRANCH } else { TREE_BRANCH };
+        let prefix_len = prefix.chars().count() + branch.chars().count();
+
+        println!(
+            "{prefix}{branch}{}",
+            format_tree_row(item, max_size, term_width, prefix_len, colorize, show_bar)
+        );
+
+        let child_prefix = format!(
+            "{prefix}{}",
+            if is_last { TREE_BLANK } else { TREE_VERTICAL }
+        );
+        print_tree_children(
+            &item.path,
+            children,
+            &child_prefix,
+            max_size,
+            term_width,
+            colorize,
+            show_bar,
+        );
+    }
+}
+
+/// Print function in tree format: an indented tree built from each entry's `depth`, with a
+/// right-aligned proportional usage bar per row. Bars are scaled to the largest entry and to
+/// the detected terminal width (falling back to 80 columns when not a TTY), and rows are
+/// colored by relative size when stdout is a terminal. When stdout isn't a terminal (e.g. piped
+/// to a file), both the color and the bar itself are omitted in favor of a plain listing.
+fn print_tree(data: Vec<PathSizeMetadata>) {
+    let Some(root) = data.iter().find(|d| d.depth == 0) else {
+        return;
+    };
+
+    let mut children: HashMap<PathBuf, Vec<&PathSizeMetadata>> = HashMap::new();
+    for item in &data {
+        if item.depth == 0 {
+            continue;
+        }
+        if let Some(parent) = item.path.parent() {
+            children
+                .entry(parent.to_path_buf())
+                .or_default()
+                .push(item);
+        }
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|d| std::cmp::Reverse(d.size));
+    }
+
+    let max_size = data.iter().map(|d| d.size).max().unwrap_or(0);
+    let show_bar = std::io::stdout().is_terminal();
+    let colorize = show_bar;
+    let term_width = terminal_size()
+        .map(|(Width(w), _)| w as usize)
+        .unwrap_or(80);
+
+    println!(
+        "{}",
+        format_tree_row(root, max_size, term_width, 0, colorize, show_bar)
+    );
+    print_tree_children(
+        &root.path, &children, "", max_size, term_width, colorize, show_bar,
+    );
+}
+
+/// Options controlling how a path is scanned, grouped into one struct so [`get_disk_usage`] and
+/// [`log_disk_usage`] don't keep growing a positional parameter per feature.
+pub struct ScanOptions<'f> {
+    /// The maximum recursive depth to keep entries for.
+    pub depth: u16,
+    /// Size of the worker thread pool; `None` defaults to
+    /// [`std::thread::available_parallelism`].
+    pub threads: Option<usize>,
+    /// When `true`, a file's size is only counted once, the first time one of its hard links
+    /// is observed, so totals reflect apparent disk footprint rather than the sum of every
+    /// link. When `false`, every link is counted separately, matching the historical
+    /// (non-deduplicated) behavior.
+    pub dedupe: bool,
+    /// Prunes and restricts the scan; see [`PathFilters`].
+    pub filters: &'f PathFilters,
+    /// Picks the size source: `true` reports allocated blocks on disk (matching how `du`
+    /// reports actual space consumed), `false` reports the logical/apparent length.
+    pub disk_usage: bool,
+}
+
 /// Get the current disk usage of a provided path. If the provided path is a path to a file, return the file size.
 /// If the provided path is the path to a directory, return the vector of all subdirectory and file sizes within,
 /// and filter out unnecessary data based on the depth.
-pub fn get_disk_usage(path: PathBuf, depth: u16) -> Vec<PathSizeMetadata> {
+///
+/// Directories are walked in parallel by a pool of worker threads, or sequentially when the pool
+/// size is 1 or less; see [`ScanOptions`] for what each option controls.
+pub fn get_disk_usage(path: PathBuf, options: &ScanOptions) -> Vec<PathSizeMetadata> {
     match path.is_dir() {
-        true => get_dir_data(path, Depth::None)
-            .unwrap()
-            .0
-            .into_iter()
-            .filter(|data| data.depth <= depth)
-            .collect(),
-        false => vec![get_file_size(path, &Depth::Depth(0)).unwrap()],
+        true => {
+            let threads = options.threads.unwrap_or_else(|| {
+                std::thread::available_parallelism()
+                    .map(|n| n.get())
+                    .unwrap_or(1)
+            });
+
+            let data = if threads <= 1 {
+                get_dir_data(
+                    path,
+                    Depth::None,
+                    options.dedupe,
+                    &mut HashSet::new(),
+                    options.filters,
+                    options.disk_usage,
+                )
+                .unwrap()
+            } else {
+                parallel::get_dir_data_parallel(
+                    path,
+                    threads,
+                    options.dedupe,
+                    options.filters,
+                    options.disk_usage,
+                )
+                .unwrap()
+            };
+
+            data.0
+                .into_iter()
+                .filter(|data| data.depth <= options.depth)
+                .collect()
+        }
+        false => vec![get_file_size(path, &Depth::Depth(0), options.disk_usage).unwrap()],
     }
 }
 
-/// Log disk usage for a given depth and path.
-pub fn log_disk_usage(path: PathBuf, depth: u16, human_readable: bool, sort: bool) {
-    let mut res: Vec<PathSizeMetadata> = get_disk_usage(path, depth);
+/// Print a set of disk usage results in one of the flat bytes/human-readable formats, or as a
+/// tree. `tree` takes precedence over `human_readable` and `sort`: it renders its own indented,
+/// size-sorted tree view instead of the flat listing.
+pub fn print_disk_usage(mut res: Vec<PathSizeMetadata>, human_readable: bool, sort: bool, tree: bool) {
+    if tree {
+        return print_tree(res);
+    }
 
     if sort {
         res.sort_by_key(|d| d.size);
@@ -221,3 +555,137 @@ pub fn log_disk_usage(path: PathBuf, depth: u16, human_readable: bool, sort: boo
         print_bytes(res)
     }
 }
+
+/// Print only the entries whose size changed between a cached scan and a fresh one, with the
+/// delta shown per path.
+pub fn print_scan_diff(diffs: &[ScanDiff], human_readable: bool) {
+    for diff in diffs {
+        let delta = diff.current_size as i64 - diff.previous_size as i64;
+        let sign = if delta >= 0 { "+" } else { "-" };
+        let magnitude = delta.unsigned_abs();
+
+        let label = if human_readable {
+            format!("{sign}{}", human_readable_size(magnitude))
+        } else {
+            format!("{sign}{magnitude}")
+        };
+
+        println!("{label:<8}  {}", diff.path.display());
+    }
+}
+
+/// Scan a path and print the results; see [`ScanOptions`] for what each scan option controls.
+pub fn log_disk_usage(
+    path: PathBuf,
+    options: &ScanOptions,
+    human_readable: bool,
+    sort: bool,
+    tree: bool,
+) {
+    let res: Vec<PathSizeMetadata> = get_disk_usage(path, options);
+    print_disk_usage(res, human_readable, sort, tree)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A fresh, uniquely-named directory under the system temp dir for a single test to build
+    /// its fixture tree in.
+    fn temp_test_dir(label: &str) -> PathBuf {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+        let dir = std::env::temp_dir().join(format!("rdu-test-{label}-{}-{n}", std::process::id()));
+        fs::create_dir_all(&dir).expect("failed to create fixture dir");
+        dir
+    }
+
+    fn total_size(root: PathBuf, filters: &PathFilters, threads: usize) -> u64 {
+        let options = ScanOptions {
+            depth: u16::MAX,
+            threads: Some(threads),
+            dedupe: false,
+            filters,
+            disk_usage: false,
+        };
+        get_disk_usage(root, &options)
+            .into_iter()
+            .find(|d| d.depth == 0)
+            .map(|d| d.size)
+            .unwrap_or(0)
+    }
+
+    /// Excluded directories should be pruned from the total entirely, while a non-matching
+    /// `--include` pattern should only drop files (directories still roll their children up),
+    /// in both the sequential and parallel traversal paths.
+    fn assert_filtered_traversal(threads: usize) {
+        let dir = temp_test_dir("filter");
+        fs::create_dir_all(dir.join("kept")).unwrap();
+        fs::create_dir_all(dir.join("skipped")).unwrap();
+        fs::write(dir.join("kept/a.txt"), b"hello").unwrap();
+        fs::write(dir.join("kept/b.log"), b"world!").unwrap();
+        fs::write(dir.join("skipped/c.txt"), b"ignored").unwrap();
+
+        let no_filters = PathFilters::none();
+        let full_total = total_size(dir.clone(), &no_filters, threads);
+        assert_eq!(full_total, 5 + 6 + 7);
+
+        let excludes = PathFilters::new(&["*/skipped".to_string()], &[], false).unwrap();
+        let excluded_total = total_size(dir.clone(), &excludes, threads);
+        assert_eq!(excluded_total, 5 + 6);
+
+        let includes = PathFilters::new(&[], &["*.txt".to_string()], false).unwrap();
+        let included_total = total_size(dir.clone(), &includes, threads);
+        assert_eq!(included_total, 5 + 7);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn filtered_traversal_sequential() {
+        assert_filtered_traversal(1);
+    }
+
+    #[test]
+    fn filtered_traversal_parallel() {
+        assert_filtered_traversal(4);
+    }
+
+    #[cfg(unix)]
+    fn assert_dedupe_hardlinks(threads: usize) {
+        let dir = temp_test_dir("dedupe");
+        fs::write(dir.join("original.txt"), b"duplicated").unwrap();
+        fs::hard_link(dir.join("original.txt"), dir.join("linked.txt")).unwrap();
+
+        let filters = PathFilters::none();
+        let options = ScanOptions {
+            depth: u16::MAX,
+            threads: Some(threads),
+            dedupe: true,
+            filters: &filters,
+            disk_usage: false,
+        };
+        let res = get_disk_usage(dir.clone(), &options);
+        let total = res
+            .iter()
+            .find(|d| d.depth == 0)
+            .map(|d| d.size)
+            .unwrap_or(0);
+
+        assert_eq!(total, 10);
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dedupe_hardlinks_counts_each_inode_once_sequential() {
+        assert_dedupe_hardlinks(1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn dedupe_hardlinks_counts_each_inode_once_parallel() {
+        assert_dedupe_hardlinks(4);
+    }
+}
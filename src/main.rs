@@ -1,6 +1,9 @@
 use clap::Parser;
-use rdu::{log_disk_usage, normalize_path_arg};
-use std::path::PathBuf;
+use rdu::{
+    diff_scan, get_disk_usage, load_scan, normalize_path_arg, print_disk_usage, print_scan_diff,
+    save_scan, PathFilters, ScanOptions,
+};
+use std::path::{Path, PathBuf};
 
 #[derive(Parser, Debug)]
 #[clap(version, about, long_about = None)]
@@ -16,16 +19,115 @@ struct Args {
     #[clap(short, value_parser)]
     /// Make output human readable
     human_readable: bool,
+
+    #[clap(short, value_parser)]
+    /// Sort output by size, ascending
+    sort: bool,
+
+    #[clap(short = 'j', long, value_parser)]
+    /// Number of worker threads to traverse directories with. Defaults to the number of
+    /// available CPUs.
+    threads: Option<usize>,
+
+    #[clap(long, value_parser)]
+    /// Count each hard-linked file once instead of once per link, matching apparent disk usage.
+    dedupe_hardlinks: bool,
+
+    #[clap(short, long, value_parser)]
+    /// Render output as an indented tree with proportional usage bars instead of a flat list.
+    tree: bool,
+
+    #[clap(long, value_parser)]
+    /// Prune a path from the scan entirely (skipping its contents). Can be given multiple
+    /// times. Glob syntax by default; see `--regex`.
+    exclude: Vec<String>,
+
+    #[clap(long, value_parser)]
+    /// Restrict counted files to those matching a pattern. Can be given multiple times.
+    /// Glob syntax by default; see `--regex`.
+    include: Vec<String>,
+
+    #[clap(long, value_parser)]
+    /// Interpret `--exclude`/`--include` patterns as regular expressions instead of globs.
+    regex: bool,
+
+    #[clap(long, value_parser)]
+    /// Write the completed scan to `<file>` as a binary cache, so it can be printed again with
+    /// `--load` or compared against with `--diff` without rescanning.
+    save: Option<String>,
+
+    #[clap(long, value_parser)]
+    /// Print a scan previously written with `--save` from `<file>`, without rescanning.
+    load: Option<String>,
+
+    #[clap(long, value_parser)]
+    /// Rescan and print only the entries whose size changed since the scan cached in `<file>`.
+    diff: Option<String>,
+
+    #[clap(long, value_parser)]
+    /// Report allocated blocks on disk instead of each file's apparent (logical) length,
+    /// matching how `du` reports actual space consumed. The default is apparent size.
+    disk_usage: bool,
 }
 
 fn main() {
     let cli = Args::parse();
     let depth = cli.max_depth.unwrap_or(0);
     let human_readable = cli.human_readable;
-    let root_path = match cli.path {
-        Some(s) => PathBuf::from(&normalize_path_arg(&s)),
+    let root_path = match &cli.path {
+        Some(s) => PathBuf::from(&normalize_path_arg(s)),
         None => PathBuf::from(&normalize_path_arg("./")),
     };
 
-    log_disk_usage(root_path, depth, human_readable);
+    if let Some(load_path) = &cli.load {
+        let (_, data) = load_scan(Path::new(load_path)).expect("failed to load scan cache");
+        // Caches are always saved at full depth, so `-d` has to be re-applied here the same
+        // way the direct-scan path applies it via `get_disk_usage`'s internal filter.
+        let data: Vec<_> = data.into_iter().filter(|d| d.depth <= depth).collect();
+        print_disk_usage(data, human_readable, cli.sort, cli.tree);
+        return;
+    }
+
+    let filters = PathFilters::new(&cli.exclude, &cli.include, cli.regex)
+        .unwrap_or_else(|e| panic!("{}", e));
+
+    if let Some(diff_path) = &cli.diff {
+        let (_, cached) = load_scan(Path::new(diff_path)).expect("failed to load scan cache");
+        // Always diff against a full-depth scan, regardless of `-d`, so a shallow display
+        // depth doesn't make unrelated changes deeper in the tree disappear from the diff.
+        let fresh_options = ScanOptions {
+            depth: u16::MAX,
+            threads: cli.threads,
+            dedupe: cli.dedupe_hardlinks,
+            filters: &filters,
+            disk_usage: cli.disk_usage,
+        };
+        let fresh = get_disk_usage(root_path, &fresh_options);
+        print_scan_diff(&diff_scan(&cached, &fresh), human_readable);
+        return;
+    }
+
+    // `--save` persists a cache meant to be reloaded later (possibly with a different `-d`),
+    // so it always captures the full tree rather than whatever depth this invocation displays.
+    let save_depth = if cli.save.is_some() { u16::MAX } else { depth };
+    let scan_options = ScanOptions {
+        depth: save_depth,
+        threads: cli.threads,
+        dedupe: cli.dedupe_hardlinks,
+        filters: &filters,
+        disk_usage: cli.disk_usage,
+    };
+    let res = get_disk_usage(root_path.clone(), &scan_options);
+
+    if let Some(save_path) = &cli.save {
+        save_scan(Path::new(save_path), &root_path, &res).expect("failed to save scan cache");
+    }
+
+    let res = if save_depth != depth {
+        res.into_iter().filter(|d| d.depth <= depth).collect()
+    } else {
+        res
+    };
+
+    print_disk_usage(res, human_readable, cli.sort, cli.tree);
 }
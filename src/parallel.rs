@@ -0,0 +1,239 @@
+//! Parallel, work-stealing directory traversal.
+//!
+//! The sequential walker in [`crate::get_dir_data`] recurses on a single thread, which leaves
+//! most machines idle on large trees. This module seeds a shared [`Injector`] with the root
+//! directory and lets a fixed pool of worker threads pop directories, `read_dir` them, and push
+//! any child directories they find back onto the queue (or steal from one another once the
+//! queue runs dry). Per-directory totals are accumulated into a concurrent map keyed by path and
+//! folded into their parents in a final sequential rollup pass once every directory has been
+//! scanned.
+
+use crossbeam::deque::{Injector, Steal, Stealer, Worker};
+use crossbeam::utils::Backoff;
+use dashmap::DashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::{get_file_size, Depth, PathFilters, PathSizeMetadata};
+
+/// A single directory awaiting traversal, paired with the parent it must roll its total up
+/// into once its own children have finished.
+struct WorkItem {
+    path: PathBuf,
+    depth: u16,
+    parent: Option<PathBuf>,
+}
+
+/// State shared across all worker threads for one parallel scan.
+struct Shared<'f> {
+    injector: Injector<WorkItem>,
+    stealers: Vec<Stealer<WorkItem>>,
+    /// Number of work items pushed but not yet fully scanned. Workers exit once this reaches
+    /// zero and they find no task in their local queue, the injector, or a sibling's queue.
+    pending: AtomicUsize,
+    /// Size contributed directly by files in a directory, not counting subdirectories.
+    own_sizes: DashMap<PathBuf, u64>,
+    /// Child directories discovered under each directory, used for the final rollup.
+    children: DashMap<PathBuf, Vec<PathBuf>>,
+    /// File-level metadata, keyed by the directory that contains them.
+    files: DashMap<PathBuf, Vec<PathSizeMetadata>>,
+    depths: DashMap<PathBuf, u16>,
+    /// When `dedupe` is set, a file's size only counts toward its directory's total the first
+    /// time its `(device, inode)` pair is inserted here.
+    dedupe: bool,
+    seen_inodes: DashMap<(u64, u64), ()>,
+    /// Prunes traversal and restricts which files contribute to totals; see [`PathFilters`].
+    filters: &'f PathFilters,
+    /// When `true`, files report allocated blocks on disk instead of their logical length.
+    disk_usage: bool,
+}
+
+/// Pop a work item from the local queue, falling back to stealing a batch from the injector
+/// and then from sibling workers.
+fn find_task(local: &Worker<WorkItem>, shared: &Shared) -> Option<WorkItem> {
+    local.pop().or_else(|| loop {
+        let stolen = shared
+            .injector
+            .steal_batch_and_pop(local)
+            .or_else(|| shared.stealers.iter().map(|s| s.steal()).collect());
+
+        match stolen {
+            Steal::Success(item) => break Some(item),
+            Steal::Empty => break None,
+            Steal::Retry => continue,
+        }
+    })
+}
+
+/// Scan a single directory: sum up its own files and hand its subdirectories back to the
+/// queue for other workers to pick up.
+fn process_item(item: WorkItem, local: &Worker<WorkItem>, shared: &Shared) {
+    shared.depths.insert(item.path.clone(), item.depth);
+
+    if let Some(parent) = &item.parent {
+        shared
+            .children
+            .entry(parent.clone())
+            .or_default()
+            .push(item.path.clone());
+    }
+
+    let entries = match crate::read_dir_contents(&item.path) {
+        Ok(entries) => entries,
+        Err(_e) => {
+            shared.pending.fetch_sub(1, Ordering::AcqRel);
+            return;
+        }
+    };
+
+    let mut own_size: u64 = 0;
+
+    for path in entries {
+        if shared.filters.is_excluded(&path) {
+            continue;
+        }
+
+        if path.is_dir() {
+            shared.pending.fetch_add(1, Ordering::AcqRel);
+            let child = WorkItem {
+                path,
+                depth: item.depth + 1,
+                parent: Some(item.path.clone()),
+            };
+            local.push(child);
+        } else if shared.filters.is_included(&path) {
+            match get_file_size(path, &Depth::Depth(item.depth + 1), shared.disk_usage) {
+                Ok(data) => {
+                    let counts_toward_total = match (shared.dedupe, data.inode) {
+                        (true, Some(inode)) => shared.seen_inodes.insert(inode, ()).is_none(),
+                        _ => true,
+                    };
+
+                    if counts_toward_total {
+                        own_size += data.size;
+                    }
+                    shared
+                        .files
+                        .entry(item.path.clone())
+                        .or_default()
+                        .push(data);
+                }
+                Err(_e) => {}
+            }
+        }
+    }
+
+    shared.own_sizes.insert(item.path, own_size);
+    shared.pending.fetch_sub(1, Ordering::AcqRel);
+}
+
+/// Fold a directory's own size and its children's rolled-up totals into a single total,
+/// memoizing as it goes so each directory is only summed once.
+fn rollup(
+    path: &PathBuf,
+    shared: &Shared,
+    totals: &DashMap<PathBuf, u64>,
+) -> u64 {
+    if let Some(total) = totals.get(path) {
+        return *total;
+    }
+
+    let own = shared.own_sizes.get(path).map(|s| *s).unwrap_or(0);
+    let children_total: u64 = match shared.children.get(path) {
+        Some(kids) => kids.iter().map(|kid| rollup(kid, shared, totals)).sum(),
+        None => 0,
+    };
+
+    let total = own + children_total;
+    totals.insert(path.clone(), total);
+    total
+}
+
+/// Traverse `root` using a pool of worker threads, returning the same
+/// `(metadata, total size)` shape as the sequential [`crate::get_dir_data`].
+///
+/// `threads` controls the worker pool size; callers typically default it to
+/// [`std::thread::available_parallelism`].
+pub(crate) fn get_dir_data_parallel<'a>(
+    root: PathBuf,
+    threads: usize,
+    dedupe: bool,
+    filters: &PathFilters,
+    disk_usage: bool,
+) -> Result<(Vec<PathSizeMetadata>, u64), &'a str> {
+    if !root.is_dir() {
+        return Err("Provided path is not a directory");
+    }
+
+    let workers: Vec<Worker<WorkItem>> = (0..threads.max(1)).map(|_| Worker::new_fifo()).collect();
+    let stealers: Vec<Stealer<WorkItem>> = workers.iter().map(|w| w.stealer()).collect();
+
+    let shared = Shared {
+        injector: Injector::new(),
+        stealers,
+        pending: AtomicUsize::new(1),
+        own_sizes: DashMap::new(),
+        children: DashMap::new(),
+        files: DashMap::new(),
+        depths: DashMap::new(),
+        dedupe,
+        seen_inodes: DashMap::new(),
+        filters,
+        disk_usage,
+    };
+
+    shared.injector.push(WorkItem {
+        path: root.clone(),
+        depth: 0,
+        parent: None,
+    });
+
+    thread::scope(|scope| {
+        for local in workers {
+            let shared = &shared;
+            scope.spawn(move || {
+                let backoff = Backoff::new();
+                loop {
+                    match find_task(&local, shared) {
+                        Some(item) => {
+                            backoff.reset();
+                            process_item(item, &local, shared);
+                        }
+                        None => {
+                            if shared.pending.load(Ordering::Acquire) == 0 {
+                                break;
+                            }
+                            backoff.snooze();
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    let totals: DashMap<PathBuf, u64> = DashMap::new();
+    let root_size = rollup(&root, &shared, &totals);
+
+    let mut metadata: Vec<PathSizeMetadata> = Vec::new();
+    for entry in shared.files.iter() {
+        metadata.extend(entry.value().iter().map(|m| PathSizeMetadata {
+            path: m.path.clone(),
+            size: m.size,
+            depth: m.depth,
+            inode: m.inode,
+        }));
+    }
+    for entry in totals.iter() {
+        let path = entry.key().clone();
+        let depth = *shared.depths.get(&path).unwrap();
+        metadata.push(PathSizeMetadata {
+            path,
+            size: *entry.value(),
+            depth,
+            inode: None,
+        });
+    }
+
+    Ok((metadata, root_size))
+}